@@ -13,7 +13,9 @@
 //   MPC_CIRC_DIR=/path/to/circuit/dir LD_LIBRARY_PATH=./apps/garbled ./rust_ffi_example
 
 use std::ffi::CString;
+use std::fmt;
 use std::os::raw::{c_char, c_int, c_uchar};
+use std::path::{Path, PathBuf};
 use std::ptr;
 
 #[link(name = "garbled")]
@@ -42,43 +44,264 @@ extern "C" {
 
     /// Free memory allocated by C functions
     fn c_free_result(ptr: *mut c_uchar);
+
+    /// Fetch the calling thread's last error, set by the Go side just before it
+    /// returned `-1`. Writes up to `len - 1` bytes of the message into `buf` plus
+    /// a trailing NUL, and returns the numeric error code (0 if no error is
+    /// pending). This mirrors how `io::Error::last_os_error` reads `errno`: the
+    /// slot is thread-local, so a garbler and an evaluator running concurrently
+    /// never clobber each other's error state.
+    fn c_last_error(buf: *mut c_char, len: c_int) -> c_int;
 }
 
-/// Safe Rust wrapper for evaluator function
-pub fn evaluator(circ_dir: &str, session_id: &str, ui: &str) -> Result<Vec<u8>, String> {
-    let c_circ_dir = CString::new(circ_dir).map_err(|e| e.to_string())?;
-    let c_sid = CString::new(session_id).map_err(|e| e.to_string())?;
-    let c_ui = CString::new(ui).map_err(|e| e.to_string())?;
-
-    let mut result_ptr: *mut c_uchar = ptr::null_mut();
-    let mut result_len: c_int = 0;
-
-    let ret = unsafe {
-        c_evaluator_fn(
-            c_circ_dir.as_ptr(),
-            c_sid.as_ptr(),
-            c_ui.as_ptr(),
-            &mut result_ptr,
-            &mut result_len,
-        )
+/// Categorised failure returned by the FFI wrappers.
+///
+/// The numeric code reported by [`c_last_error`] selects the variant; anything
+/// we do not recognise is surfaced as [`MpcError::Ffi`] with the raw message so
+/// no detail from the Go side is lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MpcError {
+    /// The session id was unknown or malformed on the Go side.
+    InvalidSession,
+    /// The circuit files could not be located or parsed.
+    CircuitLoad(String),
+    /// The MPC protocol itself failed (e.g. a bad transcript).
+    Protocol(String),
+    /// An unclassified error crossing the FFI boundary.
+    Ffi(String),
+    /// An argument contained a NUL byte at the given offset and cannot be
+    /// passed as a C string.
+    InteriorNul(usize),
+}
+
+// Error codes shared with the Go side (see `c_last_error`).
+const ERR_INVALID_SESSION: c_int = 1;
+const ERR_CIRCUIT_LOAD: c_int = 2;
+const ERR_PROTOCOL: c_int = 3;
+
+impl fmt::Display for MpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpcError::InvalidSession => write!(f, "invalid session"),
+            MpcError::CircuitLoad(msg) => write!(f, "circuit load failed: {}", msg),
+            MpcError::Protocol(msg) => write!(f, "protocol error: {}", msg),
+            MpcError::Ffi(msg) => write!(f, "ffi error: {}", msg),
+            MpcError::InteriorNul(pos) => {
+                write!(f, "argument contains an interior NUL byte at offset {}", pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MpcError {}
+
+/// Read the calling thread's pending error and build a typed [`MpcError`].
+///
+/// Mirrors `io::Error::last_os_error`: we query the thread-local slot the Go
+/// library just populated and map its numeric code onto a variant, attaching
+/// the human-readable detail the Go side provided.
+fn last_error() -> MpcError {
+    let mut buf = [0 as c_char; 256];
+    let code = unsafe { c_last_error(buf.as_mut_ptr(), buf.len() as c_int) };
+
+    let msg = unsafe {
+        std::ffi::CStr::from_ptr(buf.as_ptr())
+            .to_string_lossy()
+            .into_owned()
     };
 
+    classify_error(code, msg)
+}
+
+/// Map a numeric error code and its detail message onto an [`MpcError`] variant.
+fn classify_error(code: c_int, msg: String) -> MpcError {
+    match code {
+        ERR_INVALID_SESSION => MpcError::InvalidSession,
+        ERR_CIRCUIT_LOAD => MpcError::CircuitLoad(msg),
+        ERR_PROTOCOL => MpcError::Protocol(msg),
+        _ => MpcError::Ffi(msg),
+    }
+}
+
+/// Largest argument we are willing to stage on the stack. The FFI arguments are
+/// short hex strings, so this threshold keeps the common path allocation-free.
+const STACK_C_STR_LEN: usize = 256;
+
+/// Run `f` with a NUL-terminated view of `s`, without heap-allocating for short
+/// inputs.
+///
+/// This follows std's `small_c_string::run_with_cstr`: inputs that fit in a
+/// fixed stack buffer are copied there with a trailing NUL, and only longer
+/// strings fall back to a heap [`CString`]. An interior NUL is reported as a
+/// precise [`MpcError::InteriorNul`] rather than a generic conversion error.
+fn with_c_str<R>(s: &str, f: impl FnOnce(*const c_char) -> R) -> Result<R, MpcError> {
+    let bytes = s.as_bytes();
+
+    if let Some(pos) = bytes.iter().position(|&b| b == 0) {
+        return Err(MpcError::InteriorNul(pos));
+    }
+
+    // `len < STACK_C_STR_LEN` leaves room for the trailing NUL supplied by the
+    // zero-initialised buffer.
+    if bytes.len() < STACK_C_STR_LEN {
+        let mut buf = [0_u8; STACK_C_STR_LEN];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(f(buf.as_ptr() as *const c_char))
+    } else {
+        let c = CString::new(bytes).map_err(|e| MpcError::Ffi(e.to_string()))?;
+        Ok(f(c.as_ptr()))
+    }
+}
+
+/// Owning handle to a result buffer allocated by the Go side.
+///
+/// Exposes the bytes zero-copy via [`Deref`] / [`AsRef`] and frees the
+/// underlying allocation with [`c_free_result`] on [`Drop`], so callers no
+/// longer have to remember the manual free and MPC transcripts are not copied
+/// on every round. Use [`GarbledResult::to_vec`] when an owned `Vec` is needed.
+pub struct GarbledResult {
+    ptr: *mut c_uchar,
+    len: usize,
+}
+
+impl GarbledResult {
+    /// Copy the bytes into an owned `Vec<u8>`.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+}
+
+impl std::ops::Deref for GarbledResult {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `ptr`/`len` describe the buffer the Go side handed us; it
+        // stays valid until our `Drop` frees it.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl AsRef<[u8]> for GarbledResult {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for GarbledResult {
+    fn drop(&mut self) {
+        unsafe { c_free_result(self.ptr) };
+    }
+}
+
+/// Interpret the return code and output buffer shared by both C entry points,
+/// wrapping the C-allocated payload in a [`GarbledResult`] that frees it on
+/// drop.
+fn collect_result(
+    ret: c_int,
+    result_ptr: *mut c_uchar,
+    result_len: c_int,
+) -> Result<GarbledResult, MpcError> {
     if ret != 0 {
-        return Err("c_evaluator_fn failed".to_string());
+        return Err(last_error());
     }
 
     if result_ptr.is_null() {
-        return Err("result pointer is null".to_string());
+        return Err(MpcError::Protocol("result pointer is null".to_string()));
     }
 
-    let result = unsafe {
-        let slice = std::slice::from_raw_parts(result_ptr, result_len as usize);
-        let vec = slice.to_vec();
-        c_free_result(result_ptr);
-        vec
-    };
+    Ok(GarbledResult {
+        ptr: result_ptr,
+        len: result_len as usize,
+    })
+}
+
+/// Platform separator for a list of paths, matching std's unix/other split
+/// (`b':'` on unix, `b';'` elsewhere).
+const PATH_LIST_SEPARATOR: char = if cfg!(unix) { ':' } else { ';' };
+
+/// Split a separator-delimited path list the way `PATH` is parsed, dropping
+/// empty entries.
+fn split_circ_paths(list: &str) -> Vec<PathBuf> {
+    list.split(PATH_LIST_SEPARATOR)
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Read the `MPC_CIRC_PATH` circuit search path from the environment, returning
+/// the directories in priority order (first entry wins).
+pub fn circ_paths_from_env() -> Vec<PathBuf> {
+    std::env::var("MPC_CIRC_PATH")
+        .map(|list| split_circ_paths(&list))
+        .unwrap_or_default()
+}
+
+/// Try `call` against each directory in order, stopping at the first success.
+///
+/// A [`MpcError::CircuitLoad`] means the circuit was not found in that
+/// directory, so we fall through to the next — this is what lets a read-only
+/// system circuit store be layered under a user override without requiring
+/// every directory to hold every circuit. Any other error aborts immediately.
+fn try_circ_dirs<F>(dirs: &[&Path], mut call: F) -> Result<GarbledResult, MpcError>
+where
+    F: FnMut(&str) -> Result<GarbledResult, MpcError>,
+{
+    let mut last_err = None;
+    for dir in dirs {
+        match call(circ_dir_str(dir)?) {
+            Ok(result) => return Ok(result),
+            Err(MpcError::CircuitLoad(msg)) => last_err = Some(MpcError::CircuitLoad(msg)),
+            Err(other) => return Err(other),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        MpcError::CircuitLoad(format!("no circuit directory found in {:?}", dirs))
+    }))
+}
+
+/// Convert a resolved directory back into the `&str` the C layer expects.
+fn circ_dir_str(dir: &Path) -> Result<&str, MpcError> {
+    dir.to_str().ok_or_else(|| {
+        MpcError::CircuitLoad(format!("circuit directory {:?} is not valid UTF-8", dir))
+    })
+}
+
+/// Evaluator variant that searches a list of circuit directories in order.
+pub fn evaluator_with_paths(
+    dirs: &[&Path],
+    session_id: &str,
+    ui: &str,
+) -> Result<GarbledResult, MpcError> {
+    try_circ_dirs(dirs, |dir| evaluator(dir, session_id, ui))
+}
+
+/// Garbler variant that searches a list of circuit directories in order.
+pub fn garbler_with_paths(
+    dirs: &[&Path],
+    session_id: &str,
+    ui: &str,
+    cc: &str,
+    cnum: &str,
+) -> Result<GarbledResult, MpcError> {
+    try_circ_dirs(dirs, |dir| garbler(dir, session_id, ui, cc, cnum))
+}
 
-    Ok(result)
+/// Safe Rust wrapper for evaluator function
+pub fn evaluator(circ_dir: &str, session_id: &str, ui: &str) -> Result<GarbledResult, MpcError> {
+    with_c_str(circ_dir, |c_circ_dir| {
+        with_c_str(session_id, |c_sid| {
+            with_c_str(ui, |c_ui| {
+                let mut result_ptr: *mut c_uchar = ptr::null_mut();
+                let mut result_len: c_int = 0;
+
+                let ret = unsafe {
+                    c_evaluator_fn(c_circ_dir, c_sid, c_ui, &mut result_ptr, &mut result_len)
+                };
+
+                collect_result(ret, result_ptr, result_len)
+            })?
+        })?
+    })?
 }
 
 /// Safe Rust wrapper for garbler function
@@ -88,59 +311,151 @@ pub fn garbler(
     ui: &str,
     cc: &str,
     cnum: &str,
-) -> Result<Vec<u8>, String> {
-    let c_circ_dir = CString::new(circ_dir).map_err(|e| e.to_string())?;
-    let c_sid = CString::new(session_id).map_err(|e| e.to_string())?;
-    let c_ui = CString::new(ui).map_err(|e| e.to_string())?;
-    let c_cc = CString::new(cc).map_err(|e| e.to_string())?;
-    let c_cnum = CString::new(cnum).map_err(|e| e.to_string())?;
-
-    let mut result_ptr: *mut c_uchar = ptr::null_mut();
-    let mut result_len: c_int = 0;
-
-    let ret = unsafe {
-        c_garbler_fn(
-            c_circ_dir.as_ptr(),
-            c_sid.as_ptr(),
-            c_ui.as_ptr(),
-            c_cc.as_ptr(),
-            c_cnum.as_ptr(),
-            &mut result_ptr,
-            &mut result_len,
-        )
-    };
+) -> Result<GarbledResult, MpcError> {
+    with_c_str(circ_dir, |c_circ_dir| {
+        with_c_str(session_id, |c_sid| {
+            with_c_str(ui, |c_ui| {
+                with_c_str(cc, |c_cc| {
+                    with_c_str(cnum, |c_cnum| {
+                        let mut result_ptr: *mut c_uchar = ptr::null_mut();
+                        let mut result_len: c_int = 0;
 
-    if ret != 0 {
-        return Err("c_garbler_fn failed".to_string());
+                        let ret = unsafe {
+                            c_garbler_fn(
+                                c_circ_dir,
+                                c_sid,
+                                c_ui,
+                                c_cc,
+                                c_cnum,
+                                &mut result_ptr,
+                                &mut result_len,
+                            )
+                        };
+
+                        collect_result(ret, result_ptr, result_len)
+                    })?
+                })?
+            })?
+        })?
+    })?
+}
+
+/// Process-global configuration for the FFI wrappers.
+///
+/// Reading `MPC_CIRC_DIR` ad hoc and threading `circ_dir` through every call is
+/// error-prone for library consumers. This module keeps a default circuit
+/// directory and session namespace behind a [`RwLock`], following the pattern
+/// std uses to serialise environment access: readers take the read lock while a
+/// thread reconfiguring paths takes the write lock, so a reconfiguration can
+/// never race a call mid-flight.
+pub mod mpc {
+    use super::{circ_dir_str, GarbledResult, MpcError};
+    use std::path::{Path, PathBuf};
+    use std::sync::RwLock;
+
+    /// Circuit directory used when the configuration has not been set.
+    const DEFAULT_CIRCUIT_DIR: &str = "./apps/garbled/circ_dir";
+
+    struct Config {
+        circuit_dir: String,
+        session_namespace: String,
     }
 
-    if result_ptr.is_null() {
-        return Err("result pointer is null".to_string());
+    impl Config {
+        const fn new() -> Self {
+            Config {
+                circuit_dir: String::new(),
+                session_namespace: String::new(),
+            }
+        }
     }
 
-    let result = unsafe {
-        let slice = std::slice::from_raw_parts(result_ptr, result_len as usize);
-        let vec = slice.to_vec();
-        c_free_result(result_ptr);
-        vec
-    };
+    static CONFIG: RwLock<Config> = RwLock::new(Config::new());
+
+    /// Set the default circuit directory read by the argument-less wrappers.
+    pub fn set_circuit_dir<P: AsRef<Path>>(path: P) {
+        let mut cfg = CONFIG.write().unwrap();
+        cfg.circuit_dir = path.as_ref().to_string_lossy().into_owned();
+    }
+
+    /// Get the configured circuit directory.
+    ///
+    /// When nothing has been set explicitly, the value is seeded from the
+    /// environment — `MPC_CIRC_DIR` first, then the leading entry of the
+    /// `MPC_CIRC_PATH` list — and finally the built-in default, so consumers
+    /// that never call [`set_circuit_dir`] still honour the documented env
+    /// vars without reading them ad hoc.
+    pub fn circuit_dir() -> PathBuf {
+        {
+            let cfg = CONFIG.read().unwrap();
+            if !cfg.circuit_dir.is_empty() {
+                return PathBuf::from(&cfg.circuit_dir);
+            }
+        }
+
+        if let Ok(dir) = std::env::var("MPC_CIRC_DIR") {
+            if !dir.is_empty() {
+                return PathBuf::from(dir);
+            }
+        }
+
+        if let Some(first) = super::circ_paths_from_env().into_iter().next() {
+            return first;
+        }
+
+        PathBuf::from(DEFAULT_CIRCUIT_DIR)
+    }
+
+    /// Set the default session namespace prepended to session ids.
+    pub fn set_session_namespace<S: Into<String>>(namespace: S) {
+        let mut cfg = CONFIG.write().unwrap();
+        cfg.session_namespace = namespace.into();
+    }
 
-    Ok(result)
+    /// Get the configured session namespace (empty if unset).
+    pub fn session_namespace() -> String {
+        CONFIG.read().unwrap().session_namespace.clone()
+    }
+
+    fn namespaced_session(session_id: &str) -> String {
+        let cfg = CONFIG.read().unwrap();
+        if cfg.session_namespace.is_empty() {
+            session_id.to_string()
+        } else {
+            format!("{}{}", cfg.session_namespace, session_id)
+        }
+    }
+
+    /// Evaluator using the globally configured circuit directory.
+    pub fn evaluator(session_id: &str, ui: &str) -> Result<GarbledResult, MpcError> {
+        let dir = circuit_dir();
+        let sid = namespaced_session(session_id);
+        super::evaluator(circ_dir_str(&dir)?, &sid, ui)
+    }
+
+    /// Garbler using the globally configured circuit directory.
+    pub fn garbler(
+        session_id: &str,
+        ui: &str,
+        cc: &str,
+        cnum: &str,
+    ) -> Result<GarbledResult, MpcError> {
+        let dir = circuit_dir();
+        let sid = namespaced_session(session_id);
+        super::garbler(circ_dir_str(&dir)?, &sid, ui, cc, cnum)
+    }
 }
 
 fn main() {
     println!("Testing Rust FFI bindings for Go C library\n");
 
-    // Get the circuit directory path
-    // You can pass this as a command line argument or environment variable
-    let circ_dir = std::env::var("MPC_CIRC_DIR")
-        .unwrap_or_else(|_| "./apps/garbled/circ_dir".to_string());
-
-    println!("Using circuit directory: {}\n", circ_dir);
+    // The circuit directory lives in the global config, which seeds from
+    // MPC_CIRC_DIR / MPC_CIRC_PATH when unset, so no ad hoc env reads here.
+    println!("Using circuit directory: {}\n", mpc::circuit_dir().display());
 
     // Test evaluator
     println!("Testing evaluator function...");
-    match evaluator(&circ_dir, "test_session_1", "0x1919810") {
+    match mpc::evaluator("test_session_1", "0x1919810") {
         Ok(result) => {
             println!("Evaluator result: {}", hex::encode(&result));
         }
@@ -151,8 +466,7 @@ fn main() {
 
     // Test garbler
     println!("\nTesting garbler function...");
-    match garbler(
-        &circ_dir,
+    match mpc::garbler(
         "test_session_2",
         "0x114514",
         "0x4de216d2fdc9301e5b9c78486f7109a05670d200d9e2f275ec0aad08ec42afe7",
@@ -175,3 +489,92 @@ mod hex {
             .collect::<String>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::ffi::CStr;
+
+    // Round-trip `s` through `with_c_str`, reading the NUL-terminated buffer
+    // back out so both the stack and heap paths can be checked by content.
+    fn read_back(s: &str) -> Result<String, MpcError> {
+        with_c_str(s, |p| unsafe {
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        })
+    }
+
+    #[test]
+    fn with_c_str_roundtrips_short_input() {
+        assert_eq!(read_back("0x1919810").unwrap(), "0x1919810");
+    }
+
+    #[test]
+    fn with_c_str_spans_stack_heap_boundary() {
+        // `len < STACK_C_STR_LEN` stays on the stack; 256 spills to a CString.
+        let on_stack = "a".repeat(STACK_C_STR_LEN - 1);
+        assert_eq!(read_back(&on_stack).unwrap(), on_stack);
+
+        let on_heap = "b".repeat(STACK_C_STR_LEN);
+        assert_eq!(read_back(&on_heap).unwrap(), on_heap);
+    }
+
+    #[test]
+    fn with_c_str_rejects_interior_nul() {
+        assert_eq!(read_back("ab\0cd"), Err(MpcError::InteriorNul(2)));
+    }
+
+    #[test]
+    fn split_circ_paths_drops_empty_entries() {
+        let joined = format!("a{sep}{sep}b", sep = PATH_LIST_SEPARATOR);
+        assert_eq!(
+            split_circ_paths(&joined),
+            vec![PathBuf::from("a"), PathBuf::from("b")]
+        );
+        assert!(split_circ_paths("").is_empty());
+    }
+
+    #[test]
+    fn try_circ_dirs_falls_through_on_circuit_load() {
+        let dirs = [Path::new("/first"), Path::new("/second")];
+        let tried = RefCell::new(Vec::new());
+        let result = try_circ_dirs(&dirs, |dir| {
+            tried.borrow_mut().push(dir.to_owned());
+            Err(MpcError::CircuitLoad("missing".into()))
+        });
+        assert!(matches!(result, Err(MpcError::CircuitLoad(_))));
+        assert_eq!(tried.into_inner(), vec!["/first", "/second"]);
+    }
+
+    #[test]
+    fn try_circ_dirs_aborts_on_other_error() {
+        let dirs = [Path::new("/first"), Path::new("/second")];
+        let calls = RefCell::new(0);
+        let result = try_circ_dirs(&dirs, |_| {
+            *calls.borrow_mut() += 1;
+            Err(MpcError::InvalidSession)
+        });
+        assert!(matches!(result, Err(MpcError::InvalidSession)));
+        assert_eq!(*calls.borrow(), 1);
+    }
+
+    #[test]
+    fn classify_error_maps_codes_to_variants() {
+        assert_eq!(
+            classify_error(ERR_INVALID_SESSION, String::new()),
+            MpcError::InvalidSession
+        );
+        assert_eq!(
+            classify_error(ERR_CIRCUIT_LOAD, "load".into()),
+            MpcError::CircuitLoad("load".into())
+        );
+        assert_eq!(
+            classify_error(ERR_PROTOCOL, "proto".into()),
+            MpcError::Protocol("proto".into())
+        );
+        assert_eq!(
+            classify_error(99, "other".into()),
+            MpcError::Ffi("other".into())
+        );
+    }
+}